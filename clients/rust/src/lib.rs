@@ -1,12 +1,101 @@
-use futures_util::{Stream, StreamExt, TryStreamExt};
+use futures_util::stream::SplitSink;
+use futures_util::{SinkExt, Stream, StreamExt, TryStreamExt};
 use reqwest::{header::HeaderMap, Client};
 use serde::{Deserialize, Serialize};
 use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+pub mod serve;
+
+type WsSink = SplitSink<WebSocketStream<MaybeTlsStream<TcpStream>>, Message>;
+
+/// The SSE transport's token stream, as returned by [`ApiClient::stream_chat`].
+type SseStream = Pin<Box<dyn Stream<Item = Result<StreamResponse, reqwest::Error>> + Send + 'static>>;
+
+/// The WebSocket transport's token stream, as returned by [`ApiClient::stream_chat_ws`].
+type WsStream = Pin<Box<dyn Stream<Item = Result<StreamResponse, WsError>> + Send + 'static>>;
+
+/// Cancels an in-flight [`ApiClient::stream_chat`] or [`ApiClient::stream_chat_ws`] call.
+/// Calling [`AbortHandle::abort`] (or dropping the returned stream) stops polling it promptly
+/// and drops the underlying connection; over the WebSocket transport it first sends an explicit
+/// cancel frame so the server stops generating instead of just hanging up.
+pub struct AbortHandle {
+    inner: futures_util::stream::AbortHandle,
+    ws_sink: Option<Arc<Mutex<Option<WsSink>>>>,
+}
+
+impl AbortHandle {
+    pub fn abort(&self) {
+        let sink = self.ws_sink.as_ref().and_then(|sink| sink.lock().unwrap().take());
+        if let Some(mut s) = sink {
+            tokio::spawn(async move {
+                let _ = s.send(Message::Text(r#"{"type":"cancel"}"#.to_string())).await;
+                let _ = s.close().await;
+            });
+        }
+        self.inner.abort();
+    }
+}
+
+/// `abort()` is idempotent, so dropping the handle without calling it still stops generation.
+impl Drop for AbortHandle {
+    fn drop(&mut self) {
+        self.abort();
+    }
+}
+
+/// Errors from the WebSocket transport ([`ApiClient::stream_chat_ws`]), alongside the plain
+/// `reqwest::Error` used by the HTTP/SSE transport.
+#[derive(Debug)]
+pub enum WsError {
+    // Boxed because `tungstenite::Error` is large relative to `Decode`, and `WsError` is carried
+    // around in every item of the stream returned by `stream_chat_ws`.
+    WebSocket(Box<tokio_tungstenite::tungstenite::Error>),
+    Decode(serde_json::Error),
+}
+
+impl WsError {
+    fn websocket(e: tokio_tungstenite::tungstenite::Error) -> Self {
+        WsError::WebSocket(Box::new(e))
+    }
+}
+
+impl std::fmt::Display for WsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WsError::WebSocket(e) => write!(f, "websocket error: {e}"),
+            WsError::Decode(e) => write!(f, "failed to decode stream payload: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for WsError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            WsError::WebSocket(e) => Some(e.as_ref()),
+            WsError::Decode(e) => Some(e),
+        }
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StreamResponse {
     pub text: String,
     pub done: bool,
+    /// The SSE `event:` name this came from, if the frame carried one (e.g. to tell a token
+    /// delta on the default/`message` event apart from some other server-defined event).
+    pub event_type: Option<String>,
+}
+
+/// A single turn in a conversation, owned so it can be kept around between calls.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OwnedMsg {
+    pub role: String,
+    pub content: String,
 }
 
 #[derive(Debug, Clone)]
@@ -15,8 +104,14 @@ pub struct ApiClient {
     api_key: Option<String>,
     session_id: Option<String>,
     http: Client,
+    history: Arc<Mutex<Vec<OwnedMsg>>>,
+    max_retries: u32,
+    base_delay: std::time::Duration,
 }
 
+/// Cap on the exponential backoff delay between reconnect attempts, regardless of `base_delay`.
+const MAX_RECONNECT_DELAY: std::time::Duration = std::time::Duration::from_secs(30);
+
 #[derive(Serialize)]
 struct Msg<'a> { role: &'a str, content: &'a str }
 
@@ -29,7 +124,49 @@ struct ChatRequest<'a> {
 impl ApiClient {
     pub fn new<S: Into<String>>(api_url: S, api_key: Option<String>, session_id: Option<String>) -> Result<Self, reqwest::Error> {
         let http = Client::builder().tcp_keepalive(std::time::Duration::from_secs(60)).build()?;
-        Ok(Self { api_url: api_url.into(), api_key, session_id, http })
+        Ok(Self {
+            api_url: api_url.into(),
+            api_key,
+            session_id,
+            http,
+            history: Arc::new(Mutex::new(Vec::new())),
+            max_retries: 5,
+            base_delay: std::time::Duration::from_secs_f64(1.0),
+        })
+    }
+
+    /// Configures the reconnect policy used when a stream drops mid-response: `max_retries`
+    /// reconnect attempts, backing off from `base_delay` and doubling up to [`MAX_RECONNECT_DELAY`].
+    pub fn with_retry(mut self, max_retries: u32, base_delay: std::time::Duration) -> Self {
+        self.max_retries = max_retries;
+        self.base_delay = base_delay;
+        self
+    }
+
+    /// Returns a snapshot of the conversation accumulated so far.
+    pub fn history(&self) -> Vec<OwnedMsg> {
+        self.history.lock().unwrap().clone()
+    }
+
+    /// Drops all accumulated turns, starting a fresh conversation.
+    pub fn clear_history(&self) {
+        self.history.lock().unwrap().clear();
+    }
+
+    /// Replaces the accumulated conversation with `turns`, e.g. to seed state from an external
+    /// source such as the serve mode's OpenAI-format request history.
+    pub fn set_history(&self, turns: Vec<OwnedMsg>) {
+        *self.history.lock().unwrap() = turns;
+    }
+
+    /// Clones this client's connection settings but starts with its own empty history, e.g. one
+    /// per request in [`serve`](crate::serve) so concurrent requests don't share `history`.
+    pub fn fresh_session(&self) -> Self {
+        Self { history: Arc::new(Mutex::new(Vec::new())), ..self.clone() }
+    }
+
+    fn push_history(&self, role: &str, content: String) {
+        self.history.lock().unwrap().push(OwnedMsg { role: role.to_string(), content });
     }
 
     fn headers(&self) -> HeaderMap {
@@ -43,57 +180,453 @@ impl ApiClient {
         if self.api_url.ends_with("/v1/chat") { self.api_url.clone() } else { format!("{}/v1/chat", self.api_url.trim_end_matches('/')) }
     }
 
-    pub async fn stream_chat<'a>(&'a self, message: &'a str, stream: bool) -> Result<Pin<Box<dyn Stream<Item = Result<StreamResponse, reqwest::Error>> + Send + 'a>>, reqwest::Error> {
-        let req_body = ChatRequest { messages: vec![Msg { role: "user", content: message }], stream };
+    fn ws_endpoint(&self) -> String {
+        let http_endpoint = self.endpoint();
+        if let Some(rest) = http_endpoint.strip_prefix("https://") {
+            format!("wss://{}", rest)
+        } else if let Some(rest) = http_endpoint.strip_prefix("http://") {
+            format!("ws://{}", rest)
+        } else {
+            http_endpoint
+        }
+    }
+
+    /// Opens a WebSocket connection to the same endpoint as the HTTP/SSE path, carrying the same
+    /// `X-API-Key`/`X-Session-ID` headers, sends `messages` as the first text frame, and yields
+    /// the same `{response, done}` payload decoded from each inbound text frame.
+    ///
+    /// This is an alternative to [`ApiClient::stream_chat`]'s SSE transport: bidirectional and
+    /// lower-latency, and able to carry mid-stream control frames (e.g. cancellation) that a
+    /// plain SSE response can't.
+    pub async fn stream_chat_ws(&self, message: &str) -> Result<(WsStream, AbortHandle), WsError> {
+        let mut messages = self.history();
+        messages.push(OwnedMsg { role: "user".to_string(), content: message.to_string() });
+        let msgs: Vec<Msg> = messages.iter().map(|m| Msg { role: &m.role, content: &m.content }).collect();
+        let req_body = ChatRequest { messages: msgs, stream: true };
+
+        let mut request = self.ws_endpoint().into_client_request().map_err(WsError::websocket)?;
+        request.headers_mut().extend(self.headers());
+        let (ws_stream, _) = tokio_tungstenite::connect_async(request).await.map_err(WsError::websocket)?;
+
+        let (mut write, read) = ws_stream.split();
+        let payload = serde_json::to_string(&req_body).map_err(WsError::Decode)?;
+        write.send(Message::Text(payload)).await.map_err(WsError::websocket)?;
+        self.push_history("user", message.to_string());
+
+        let ws_sink = Arc::new(Mutex::new(Some(write)));
+        let reply = Arc::new(Mutex::new(String::new()));
+        let client = self.clone();
+        let s = read.map(move |item| match item {
+            Ok(Message::Text(txt)) => {
+                let v: serde_json::Value = serde_json::from_str(&txt).map_err(WsError::Decode)?;
+                let done = v.get("done").and_then(|x| x.as_bool()).unwrap_or(false);
+                let text = v.get("response").and_then(|x| x.as_str()).unwrap_or_default().to_string();
+                if !text.is_empty() { reply.lock().unwrap().push_str(&text); }
+                if done {
+                    let acc = std::mem::take(&mut *reply.lock().unwrap());
+                    if !acc.is_empty() { client.push_history("assistant", acc); }
+                }
+                Ok(StreamResponse { text, done, event_type: None })
+            }
+            Ok(Message::Close(_)) => {
+                // The server ended the turn by closing the socket instead of sending a final
+                // `{"done":true}` text frame: flush what we've accumulated so it isn't lost from
+                // history. The same gap exists below on a transport error, where there's no way
+                // to tell a server-side failure from an intentional "stop here".
+                let acc = std::mem::take(&mut *reply.lock().unwrap());
+                if !acc.is_empty() { client.push_history("assistant", acc); }
+                Ok(StreamResponse { text: String::new(), done: true, event_type: None })
+            }
+            Ok(_) => Ok(StreamResponse { text: String::new(), done: false, event_type: None }),
+            Err(e) => Err(WsError::websocket(e)),
+        });
+
+        let (s, inner) = futures_util::stream::abortable(s);
+        let handle = AbortHandle { inner, ws_sink: Some(ws_sink) };
+        Ok((Box::pin(s), handle))
+    }
+
+    /// Opens a single SSE attempt for `messages`, sending `Last-Event-ID` when `last_event_id`
+    /// holds a value from a previous attempt, and updates it as `id:` fields arrive.
+    async fn open_sse(&self, messages: &[OwnedMsg], last_event_id: &Arc<Mutex<Option<String>>>) -> Result<SseStream, reqwest::Error> {
+        let msgs: Vec<Msg> = messages.iter().map(|m| Msg { role: &m.role, content: &m.content }).collect();
+        let req_body = ChatRequest { messages: msgs, stream: true };
         let mut req = self.http.post(self.endpoint())
             .headers(self.headers())
-            .header("Content-Type", "application/json");
-        if stream { req = req.header("Accept", "text/event-stream"); } else { req = req.header("Accept", "application/json"); }
+            .header("Content-Type", "application/json")
+            .header("Accept", "text/event-stream");
+        if let Some(id) = last_event_id.lock().unwrap().clone() { req = req.header("Last-Event-ID", id); }
         let resp = req.json(&req_body).send().await?;
 
+        let bytes_stream = resp.bytes_stream();
+        let mut parser = SseParser::new();
+        let last_event_id = last_event_id.clone();
+        let s = bytes_stream
+            .map_ok(move |chunk| {
+                parser.feed(&chunk).into_iter()
+                    .flat_map(|ev| dispatch_event(ev, &last_event_id))
+                    .collect::<Vec<_>>()
+            })
+            .map_ok(|v| futures_util::stream::iter(v.into_iter().map(Ok::<_, reqwest::Error>)))
+            .try_flatten();
+
+        Ok(Box::pin(s))
+    }
+
+    /// Sends `message` together with the conversation accumulated so far via [`ApiClient::history`],
+    /// then appends the user turn and the assembled assistant reply back into that history once the
+    /// response completes. A transport error before a `done` frame triggers a reconnect with
+    /// exponential backoff, resuming from the last seen SSE `id:` via `Last-Event-ID` so the caller
+    /// sees a single continuous stream instead of a gap.
+    ///
+    /// This assumes the backend actually resumes generation from `Last-Event-ID` rather than
+    /// restarting the turn: the reply accumulator is never reset across reconnect attempts, so if
+    /// the backend instead regenerates from scratch, the pre-disconnect text already streamed to
+    /// the caller gets concatenated with the full regenerated reply in both the live stream and
+    /// the final history entry. `Last-Event-ID` is a header this client invented for its own
+    /// backend, so this is unverified against a server that doesn't honor it.
+    pub async fn stream_chat(&self, message: &str, stream: bool) -> Result<(SseStream, AbortHandle), reqwest::Error> {
+        let mut messages = self.history();
+        messages.push(OwnedMsg { role: "user".to_string(), content: message.to_string() });
+
         if !stream {
+            let msgs: Vec<Msg> = messages.iter().map(|m| Msg { role: &m.role, content: &m.content }).collect();
+            let req_body = ChatRequest { messages: msgs, stream: false };
+            let req = self.http.post(self.endpoint())
+                .headers(self.headers())
+                .header("Content-Type", "application/json")
+                .header("Accept", "application/json");
+            let resp = req.json(&req_body).send().await?;
+            self.push_history("user", message.to_string());
+
             // Non-streaming: parse JSON { response: string }
             let v: serde_json::Value = resp.json().await?;
             let text = v.get("response").and_then(|x| x.as_str()).unwrap_or_default().to_string();
-            let s = futures_util::stream::once(async move { Ok(StreamResponse { text, done: true }) });
-            return Ok(Box::pin(s));
+            self.push_history("assistant", text.clone());
+            let s = futures_util::stream::once(async move { Ok(StreamResponse { text, done: true, event_type: None }) });
+            let (s, inner) = futures_util::stream::abortable(s);
+            return Ok((Box::pin(s), AbortHandle { inner, ws_sink: None }));
         }
 
-        let bytes_stream = resp.bytes_stream();
-        let mut buf = String::new();
-        let s = bytes_stream.map_ok(|chunk| String::from_utf8_lossy(&chunk).to_string())
-            .map_ok(move |chunk| {
-                let mut out: Vec<StreamResponse> = Vec::new();
-                buf.push_str(&chunk);
-                let mut start = 0usize;
-                while let Some(idx) = buf[start..].find('\n') {
-                    let line = buf[start..start + idx].trim().to_string();
-                    start += idx + 1;
-                    if line.is_empty() { continue; }
-                    if line.starts_with("data: ") {
-                        let data = line[6..].trim();
-                        if data.is_empty() || data == "[DONE]" { out.push(StreamResponse { text: String::new(), done: true }); continue; }
-                        if let Ok(v) = serde_json::from_str::<serde_json::Value>(data) {
-                            let done = v.get("done").and_then(|x| x.as_bool()).unwrap_or(false);
-                            if let Some(t) = v.get("response").and_then(|x| x.as_str()) {
-                                out.push(StreamResponse { text: t.to_string(), done });
-                            }
-                            if done { out.push(StreamResponse { text: String::new(), done: true }); }
+        self.push_history("user", message.to_string());
+
+        let state = ReconnectState {
+            client: self.clone(),
+            messages,
+            reply: Arc::new(Mutex::new(String::new())),
+            last_event_id: Arc::new(Mutex::new(None)),
+            inner: None,
+            attempt: 0,
+            done: false,
+        };
+        let s = futures_util::stream::unfold(state, move |mut st| async move {
+            loop {
+                if st.done { return None; }
+                if st.inner.is_none() {
+                    match st.client.open_sse(&st.messages, &st.last_event_id).await {
+                        Ok(inner) => st.inner = Some(inner),
+                        Err(e) => {
+                            if st.attempt >= st.client.max_retries { st.done = true; return Some((Err(e), st)); }
+                            tokio::time::sleep(reconnect_delay(st.attempt, st.client.base_delay)).await;
+                            st.attempt += 1;
+                            continue;
                         }
-                    } else {
-                        out.push(StreamResponse { text: line, done: false });
                     }
                 }
-                // retain the remainder
-                let rem = buf[start..].to_string();
-                buf.clear();
-                buf.push_str(&rem);
-                out
-            })
-            .map_ok(|v| futures_util::stream::iter(v.into_iter().map(Ok::<_, reqwest::Error>)))
-            .try_flatten();
+                match st.inner.as_mut().unwrap().next().await {
+                    Some(Ok(item)) => {
+                        if !item.text.is_empty() {
+                            st.reply.lock().unwrap().push_str(&item.text);
+                        }
+                        if item.done {
+                            st.done = true;
+                            let text = std::mem::take(&mut *st.reply.lock().unwrap());
+                            if !text.is_empty() { st.client.push_history("assistant", text); }
+                        }
+                        return Some((Ok(item), st));
+                    }
+                    Some(Err(e)) => {
+                        st.inner = None;
+                        if st.attempt >= st.client.max_retries { st.done = true; return Some((Err(e), st)); }
+                        tokio::time::sleep(reconnect_delay(st.attempt, st.client.base_delay)).await;
+                        st.attempt += 1;
+                        continue;
+                    }
+                    None => {
+                        // Connection closed without a done frame: the server dropped us mid-reply.
+                        st.inner = None;
+                        if st.attempt >= st.client.max_retries { return None; }
+                        tokio::time::sleep(reconnect_delay(st.attempt, st.client.base_delay)).await;
+                        st.attempt += 1;
+                        continue;
+                    }
+                }
+            }
+        });
 
-        Ok(Box::pin(s))
+        let (s, inner) = futures_util::stream::abortable(s);
+        Ok((Box::pin(s), AbortHandle { inner, ws_sink: None }))
+    }
+}
+
+struct ReconnectState {
+    client: ApiClient,
+    messages: Vec<OwnedMsg>,
+    reply: Arc<Mutex<String>>,
+    last_event_id: Arc<Mutex<Option<String>>>,
+    inner: Option<SseStream>,
+    attempt: u32,
+    done: bool,
+}
+
+/// Exponential backoff with jitter: `base * 2^attempt`, capped at [`MAX_RECONNECT_DELAY`], plus
+/// up to 249ms of jitter added after the cap.
+fn reconnect_delay(attempt: u32, base: std::time::Duration) -> std::time::Duration {
+    let scaled = base.mul_f64(2f64.powi(attempt.min(16) as i32));
+    let jitter = std::time::Duration::from_millis(
+        std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map(|d| d.subsec_millis() as u64 % 250).unwrap_or(0),
+    );
+    scaled.min(MAX_RECONNECT_DELAY) + jitter
+}
+
+/// A single SSE frame assembled from `field: value` lines up to the blank line that dispatches it.
+#[derive(Debug, Clone, Default)]
+struct SseEvent {
+    data: String,
+    event: Option<String>,
+    id: Option<String>,
+    retry: Option<u64>,
+}
+
+/// Buffers raw bytes and assembles them into complete SSE frames per the spec: lines starting
+/// with `:` are comments and ignored, one optional leading space after the field colon is
+/// stripped, multiple `data:` lines are joined with `\n`, and a blank line dispatches the
+/// accumulated event.
+///
+/// Buffering happens on raw bytes rather than `str` so that a multi-byte UTF-8 character split
+/// across a network read boundary is reassembled before decoding, instead of landing on a partial
+/// sequence and getting replaced with U+FFFD.
+#[derive(Debug, Default)]
+struct SseParser {
+    buf: Vec<u8>,
+    event: SseEvent,
+}
+
+impl SseParser {
+    fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feeds newly-received bytes and returns any events completed as a result.
+    fn feed(&mut self, chunk: &[u8]) -> Vec<SseEvent> {
+        self.buf.extend_from_slice(chunk);
+        let mut out = Vec::new();
+        let mut start = 0usize;
+        while let Some(idx) = self.buf[start..].iter().position(|&b| b == b'\n') {
+            let line_end = start + idx;
+            let raw = &self.buf[start..line_end];
+            let raw = raw.strip_suffix(b"\r").unwrap_or(raw);
+            let line = String::from_utf8_lossy(raw).into_owned();
+            start = line_end + 1;
+
+            if line.is_empty() {
+                let ev = std::mem::take(&mut self.event);
+                if !ev.data.is_empty() || ev.event.is_some() || ev.id.is_some() || ev.retry.is_some() {
+                    out.push(ev);
+                }
+                continue;
+            }
+            if line.starts_with(':') { continue; }
+
+            let (field, value) = match line.find(':') {
+                Some(i) => (&line[..i], line[i + 1..].strip_prefix(' ').unwrap_or(&line[i + 1..])),
+                None => (line.as_str(), ""),
+            };
+            match field {
+                "data" => {
+                    if !self.event.data.is_empty() { self.event.data.push('\n'); }
+                    self.event.data.push_str(value);
+                }
+                "event" => self.event.event = Some(value.to_string()),
+                "id" => self.event.id = Some(value.to_string()),
+                "retry" => self.event.retry = value.parse().ok(),
+                _ => {}
+            }
+        }
+        self.buf.drain(..start);
+        out
+    }
+}
+
+/// Handles a completed SSE frame: records its `id:` for reconnect resumption, and decodes the
+/// accumulated `data:` payload as `{response, done}` JSON on the default/`message` event, passing
+/// other event types through as raw text tagged with their `event_type`.
+fn dispatch_event(ev: SseEvent, last_event_id: &Mutex<Option<String>>) -> Vec<StreamResponse> {
+    if let Some(id) = &ev.id { *last_event_id.lock().unwrap() = Some(id.clone()); }
+    let event_type = ev.event.clone();
+    let is_default = matches!(event_type.as_deref(), None | Some("message"));
+
+    let mut out = Vec::new();
+    if ev.data.is_empty() { return out; }
+    if ev.data == "[DONE]" { out.push(StreamResponse { text: String::new(), done: true, event_type }); return out; }
+
+    if is_default {
+        if let Ok(v) = serde_json::from_str::<serde_json::Value>(&ev.data) {
+            let done = v.get("done").and_then(|x| x.as_bool()).unwrap_or(false);
+            if let Some(t) = v.get("response").and_then(|x| x.as_str()) {
+                out.push(StreamResponse { text: t.to_string(), done, event_type: event_type.clone() });
+            }
+            if done { out.push(StreamResponse { text: String::new(), done: true, event_type }); }
+        } else {
+            out.push(StreamResponse { text: ev.data, done: false, event_type });
+        }
+    } else {
+        out.push(StreamResponse { text: ev.data, done: false, event_type });
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn client() -> ApiClient {
+        ApiClient::new("http://localhost:1234", None, None).unwrap()
+    }
+
+    #[test]
+    fn history_is_empty_by_default() {
+        assert!(client().history().is_empty());
+    }
+
+    #[test]
+    fn push_history_appends_turns_in_order() {
+        let c = client();
+        c.push_history("user", "hi".to_string());
+        c.push_history("assistant", "hello".to_string());
+        let h = c.history();
+        assert_eq!(h.len(), 2);
+        assert_eq!(h[0].role, "user");
+        assert_eq!(h[1].content, "hello");
+    }
+
+    #[test]
+    fn clear_history_empties_it() {
+        let c = client();
+        c.push_history("user", "hi".to_string());
+        c.clear_history();
+        assert!(c.history().is_empty());
+    }
+
+    #[test]
+    fn set_history_replaces_it() {
+        let c = client();
+        c.push_history("user", "stale".to_string());
+        c.set_history(vec![OwnedMsg { role: "user".to_string(), content: "fresh".to_string() }]);
+        let h = c.history();
+        assert_eq!(h.len(), 1);
+        assert_eq!(h[0].content, "fresh");
+    }
+
+    #[test]
+    fn fresh_session_has_independent_history() {
+        let c = client();
+        c.push_history("user", "original".to_string());
+        let session = c.fresh_session();
+        assert!(session.history().is_empty());
+        session.push_history("user", "session-only".to_string());
+        assert_eq!(c.history().len(), 1);
+        assert_eq!(session.history().len(), 1);
+    }
+
+    #[test]
+    fn feed_joins_multiline_data() {
+        let mut parser = SseParser::new();
+        let events = parser.feed(b"data: line one\ndata: line two\n\n");
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].data, "line one\nline two");
+    }
+
+    #[test]
+    fn feed_parses_event_id_and_retry() {
+        let mut parser = SseParser::new();
+        let events = parser.feed(b"event: token\nid: 42\nretry: 3000\ndata: hi\n\n");
+        assert_eq!(events.len(), 1);
+        let ev = &events[0];
+        assert_eq!(ev.event.as_deref(), Some("token"));
+        assert_eq!(ev.id.as_deref(), Some("42"));
+        assert_eq!(ev.retry, Some(3000));
+        assert_eq!(ev.data, "hi");
+    }
+
+    #[test]
+    fn feed_ignores_comment_lines() {
+        let mut parser = SseParser::new();
+        let events = parser.feed(b": keep-alive\ndata: hi\n\n");
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].data, "hi");
+    }
+
+    #[test]
+    fn feed_strips_one_leading_space_after_colon() {
+        let mut parser = SseParser::new();
+        let events = parser.feed(b"data:no leading space\ndata:  two spaces\n\n");
+        assert_eq!(events[0].data, "no leading space\n two spaces");
+    }
+
+    #[test]
+    fn feed_buffers_partial_chunks_across_calls() {
+        let mut parser = SseParser::new();
+        assert!(parser.feed(b"data: hel").is_empty());
+        let events = parser.feed(b"lo\n\n");
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].data, "hello");
+    }
+
+    #[test]
+    fn feed_drops_blank_data_only_frame() {
+        let mut parser = SseParser::new();
+        let events = parser.feed(b"data:\n\n");
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn feed_reassembles_multibyte_utf8_split_across_chunks() {
+        let mut parser = SseParser::new();
+        let payload = "data: caf\u{e9}\n\n".as_bytes().to_vec();
+        let split = payload.len() - 3; // splits inside 'é' (0xC3 0xA9)'s two-byte encoding
+        assert!(parser.feed(&payload[..split]).is_empty());
+        let events = parser.feed(&payload[split..]);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].data, "caf\u{e9}");
+    }
+
+    #[test]
+    fn dispatch_event_decodes_default_event_json() {
+        let last_event_id = Mutex::new(None);
+        let ev = SseEvent { data: r#"{"response":"hi","done":false}"#.to_string(), ..Default::default() };
+        let out = dispatch_event(ev, &last_event_id);
+        assert_eq!(out.len(), 1);
+        assert_eq!(out[0].text, "hi");
+        assert!(!out[0].done);
+    }
+
+    #[test]
+    fn dispatch_event_tracks_last_event_id() {
+        let last_event_id = Mutex::new(None);
+        let ev = SseEvent { data: "hi".to_string(), id: Some("7".to_string()), ..Default::default() };
+        dispatch_event(ev, &last_event_id);
+        assert_eq!(*last_event_id.lock().unwrap(), Some("7".to_string()));
+    }
+
+    #[test]
+    fn reconnect_delay_doubles_then_caps() {
+        let base = std::time::Duration::from_secs_f64(1.0);
+        assert!(reconnect_delay(0, base) < std::time::Duration::from_millis(1250));
+        assert!(reconnect_delay(1, base) >= std::time::Duration::from_millis(2000));
+        assert!(reconnect_delay(1, base) < std::time::Duration::from_millis(2250));
+        // Large attempt counts must not overflow and must stay capped.
+        assert!(reconnect_delay(50, base) <= MAX_RECONNECT_DELAY + std::time::Duration::from_millis(250));
     }
 }
 
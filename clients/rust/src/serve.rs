@@ -0,0 +1,201 @@
+//! Local OpenAI-compatible server that forwards `/v1/chat/completions` requests through an
+//! [`ApiClient`], so existing OpenAI-SDK tooling can point at a local port and transparently use
+//! an Orbit backend.
+use crate::{AbortHandle, ApiClient, OwnedMsg, SseStream};
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::sse::{Event, KeepAlive};
+use axum::response::{IntoResponse, Response, Sse};
+use axum::routing::post;
+use axum::{Json, Router};
+use futures_util::{Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+#[derive(Deserialize)]
+struct OpenAiMessage {
+    role: String,
+    content: String,
+}
+
+#[derive(Deserialize)]
+struct OpenAiChatRequest {
+    messages: Vec<OpenAiMessage>,
+    #[serde(default)]
+    stream: bool,
+}
+
+#[derive(Serialize)]
+struct Delta {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    content: Option<String>,
+}
+
+#[derive(Serialize)]
+struct ChunkChoice {
+    index: u32,
+    delta: Delta,
+    finish_reason: Option<&'static str>,
+}
+
+#[derive(Serialize)]
+struct ChatCompletionChunk {
+    id: String,
+    object: &'static str,
+    choices: Vec<ChunkChoice>,
+}
+
+#[derive(Serialize)]
+struct Message {
+    role: &'static str,
+    content: String,
+}
+
+#[derive(Serialize)]
+struct Choice {
+    index: u32,
+    message: Message,
+    finish_reason: &'static str,
+}
+
+#[derive(Serialize)]
+struct ChatCompletion {
+    id: String,
+    object: &'static str,
+    choices: Vec<Choice>,
+}
+
+/// Runs the server on `addr` until the process receives Ctrl-C. `client` only supplies the
+/// connection settings (base URL, API key, retry policy); each request gets its own
+/// [`ApiClient::fresh_session`] seeded from that request's own `messages`.
+pub async fn serve(client: ApiClient, addr: SocketAddr) -> std::io::Result<()> {
+    let state = Arc::new(client);
+    let app = Router::new()
+        .route("/v1/chat/completions", post(chat_completions))
+        .with_state(state);
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    axum::serve(listener, app)
+        .with_graceful_shutdown(async {
+            let _ = tokio::signal::ctrl_c().await;
+        })
+        .await
+}
+
+/// Splits an OpenAI-style message list into history (everything but the last message) and the
+/// user's current turn (the last message's content). Returns `None` if `messages` is empty.
+fn split_last_turn(messages: Vec<OpenAiMessage>) -> Option<(Vec<OwnedMsg>, String)> {
+    let mut turns: Vec<OwnedMsg> = messages.into_iter().map(|m| OwnedMsg { role: m.role, content: m.content }).collect();
+    let last = turns.pop()?;
+    Some((turns, last.content))
+}
+
+async fn chat_completions(State(client): State<Arc<ApiClient>>, Json(req): Json<OpenAiChatRequest>) -> Response {
+    let session = client.fresh_session();
+
+    let (turns, message) = match split_last_turn(req.messages) {
+        Some(v) => v,
+        None => return (StatusCode::BAD_REQUEST, "messages must not be empty").into_response(),
+    };
+    session.set_history(turns);
+
+    let (inner, abort) = match session.stream_chat(&message, req.stream).await {
+        Ok(v) => v,
+        Err(e) => return (StatusCode::BAD_GATEWAY, e.to_string()).into_response(),
+    };
+
+    if !req.stream {
+        return match aggregate(inner).await {
+            Ok(text) => Json(ChatCompletion {
+                id: "chatcmpl-orbit".to_string(),
+                object: "chat.completion",
+                choices: vec![Choice { index: 0, message: Message { role: "assistant", content: text }, finish_reason: "stop" }],
+            })
+            .into_response(),
+            Err(e) => (StatusCode::BAD_GATEWAY, e.to_string()).into_response(),
+        };
+    }
+
+    Sse::new(completion_chunks(inner, abort)).keep_alive(KeepAlive::default()).into_response()
+}
+
+async fn aggregate(mut inner: SseStream) -> Result<String, reqwest::Error> {
+    let mut text = String::new();
+    while let Some(item) = inner.next().await {
+        let r = item?;
+        text.push_str(&r.text);
+        if r.done { break; }
+    }
+    Ok(text)
+}
+
+/// Re-emits `inner`'s token deltas as OpenAI-format `data: {...}` chunks, closing with `data: [DONE]`.
+/// `abort` rides along in the unfold state purely so its `Drop` impl fires if axum drops this
+/// stream early (client disconnected mid-response).
+fn completion_chunks(inner: SseStream, abort: AbortHandle) -> impl Stream<Item = Result<Event, Infallible>> {
+    futures_util::stream::unfold((inner, "chatcmpl-orbit".to_string(), abort, false), |(mut inner, id, abort, done)| async move {
+        if done { return None; }
+        match inner.next().await {
+            Some(Ok(r)) if r.done && r.text.is_empty() => Some((Ok(Event::default().data("[DONE]")), (inner, id, abort, true))),
+            Some(Ok(r)) => {
+                let chunk = ChatCompletionChunk {
+                    id: id.clone(),
+                    object: "chat.completion.chunk",
+                    choices: vec![ChunkChoice { index: 0, delta: Delta { content: Some(r.text) }, finish_reason: None }],
+                };
+                let json = serde_json::to_string(&chunk).unwrap_or_default();
+                Some((Ok(Event::default().data(json)), (inner, id, abort, false)))
+            }
+            Some(Err(_)) | None => Some((Ok(Event::default().data("[DONE]")), (inner, id, abort, true))),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::StreamResponse;
+
+    fn msg(role: &str, content: &str) -> OpenAiMessage {
+        OpenAiMessage { role: role.to_string(), content: content.to_string() }
+    }
+
+    #[test]
+    fn split_last_turn_separates_history_from_current_message() {
+        let (history, message) = split_last_turn(vec![
+            msg("user", "hi"),
+            msg("assistant", "hello"),
+            msg("user", "how are you"),
+        ])
+        .unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].content, "hi");
+        assert_eq!(history[1].content, "hello");
+        assert_eq!(message, "how are you");
+    }
+
+    #[test]
+    fn split_last_turn_with_single_message_has_empty_history() {
+        let (history, message) = split_last_turn(vec![msg("user", "hi")]).unwrap();
+        assert!(history.is_empty());
+        assert_eq!(message, "hi");
+    }
+
+    #[test]
+    fn split_last_turn_rejects_empty_messages() {
+        assert!(split_last_turn(vec![]).is_none());
+    }
+
+    #[tokio::test]
+    async fn aggregate_concatenates_text_until_done() {
+        let items: Vec<Result<StreamResponse, reqwest::Error>> = vec![
+            Ok(StreamResponse { text: "Hel".to_string(), done: false, event_type: None }),
+            Ok(StreamResponse { text: "lo".to_string(), done: false, event_type: None }),
+            Ok(StreamResponse { text: String::new(), done: true, event_type: None }),
+        ];
+        let stream: SseStream = Box::pin(futures_util::stream::iter(items));
+        let text = aggregate(stream).await.unwrap();
+        assert_eq!(text, "Hello");
+    }
+}